@@ -1,7 +1,9 @@
 // SPDX-License-Identifier: GPL-2.0
 
+use kernel::bindings;
 use kernel::i2c::{self, DeviceId};
 use kernel::prelude::*;
+use kernel::watchdog::{self, WatchdogDevice};
 
 kernel::module_i2c_driver! {
     type: MComFPGA,
@@ -14,10 +16,16 @@ kernel::module_i2c_driver! {
     "i2c:fpga"
 ]*/
 
+/// Port-direction register: writing a bit as `0` makes the corresponding pin an output.
+const REG_PORT_DIR: u8 = 0x06;
+
+/// Watchdog kick register: any write pets the hardware watchdog.
+const REG_WDT_KICK: u8 = 0x00;
+
 struct MComFPGA;
 
 impl i2c::Driver for MComFPGA {
-    type Data = ();
+    type Data = Box<WatchdogDevice<Device>>;
 
     kernel::define_i2c_id_table! {(), [(DeviceId(b"fpga"), None),]}
 
@@ -29,10 +37,74 @@ impl i2c::Driver for MComFPGA {
             return Err(code::EINVAL);
         }
 
-        // Configure port 0 as output
-        let cmd = [0x06, 0x00];
-        client.master_send(&buf);
+        // Configure port 0 as output.
+        client.master_send(&[REG_PORT_DIR, 0x00])?;
+
+        let wdd = WatchdogDevice::register(
+            client,
+            kernel::c_str!("mcom_fpga_wdt"),
+            1,
+            255,
+            &THIS_MODULE,
+            Box::try_new(Device {
+                client: client.as_raw(),
+            })?,
+        )?;
+
+        Ok(Box::try_new(wdd)?)
+    }
+}
+
+/// Driver data shared with the [`watchdog::WatchdogOps`] callbacks.
+///
+/// Stores the client as a raw pointer rather than an owned [`i2c::Client`] handle: the watchdog
+/// core only ever calls back with a shared `&Device`, and `i2c::Client` is deliberately not
+/// `Copy` (its transfer methods such as `master_send` need `&mut self` exclusivity). Reaching
+/// the client is therefore an explicit, audited `unsafe` step (see [`Self::client`]) instead of
+/// a silent copy.
+struct Device {
+    client: *mut bindings::i2c_client,
+}
+
+// SAFETY: the i2c core refcounts the underlying `i2c_client` for as long as the driver is
+// bound, and the watchdog core never calls back into a removed device.
+unsafe impl Send for Device {}
+// SAFETY: the watchdog core serialises calls into `WatchdogOps`, so the raw pointer is only
+// ever turned into a `Client` from one thread at a time.
+unsafe impl Sync for Device {}
+
+impl Device {
+    /// Reconstructs the [`i2c::Client`] handle for this device.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure no other `Client` handle for the same underlying `i2c_client` is
+    /// in use concurrently. The watchdog core serialising calls into [`watchdog::WatchdogOps`]
+    /// is what makes every call site below sound.
+    unsafe fn client(&self) -> i2c::Client {
+        // SAFETY: `self.client` was obtained from a live `i2c::Client` in `probe` and stays
+        // valid for as long as this `Device` is alive, which the caller's obligations above
+        // guarantee doesn't overlap with another handle to it.
+        unsafe { i2c::Client::from_raw(self.client) }
+    }
+}
+
+#[vtable]
+impl watchdog::WatchdogOps for Device {
+    type Data = Box<Device>;
+
+    fn start(data: &Device) -> Result {
+        Self::ping(data)
+    }
+
+    fn stop(_data: &Device) -> Result {
+        Ok(())
+    }
 
+    fn ping(data: &Device) -> Result {
+        // SAFETY: see `Device::client`.
+        let mut client = unsafe { data.client() };
+        client.master_send(&[REG_WDT_KICK, 0x00])?;
         Ok(())
     }
 }
@@ -4,11 +4,18 @@
 //!
 //! C header: [`include/linux/i2c.h`](../../../../include/linux/i2c.h)
 
+use alloc::boxed::Box;
+use core::marker;
+
+use macros::vtable;
+
 use crate::{
+    acpi,
     bindings,
     device::RawDevice,
     driver,
     error::{code, from_kernel_result, Result},
+    of,
     str::{BStr, CStr},
     to_result,
     types::PointerWrapper,
@@ -82,6 +89,12 @@ impl<T: Driver> driver::DriverOps for Adapter<T> {
         if let Some(t) = T::I2C_DEVICE_ID_TABLE {
             i2cdrv.id_table = t.as_ref();
         }
+        if let Some(t) = T::OF_DEVICE_ID_TABLE {
+            i2cdrv.driver.of_match_table = t.as_ref();
+        }
+        if let Some(t) = T::ACPI_DEVICE_ID_TABLE {
+            i2cdrv.driver.acpi_match_table = t.as_ref();
+        }
 
         // SAFETY:
         //   - `pdrv` lives at least until the call to `platform_driver_unregister()` returns.
@@ -102,35 +115,80 @@ impl<T: Driver> driver::DriverOps for Adapter<T> {
 }
 
 impl<T: Driver> Adapter<T> {
+    /// Recovers the `T::IdInfo` stashed `offset` bytes after `id` by [`IdArray::new`], as done
+    /// for every kind of id table this driver may be matched through.
+    ///
+    /// # Safety
+    ///
+    /// `id` must point to a live entry of a static `IdArray`-generated table, and `offset` must
+    /// be the byte offset that `RawDeviceId::to_rawid` stored for that very entry (zero meaning
+    /// "no associated `IdInfo`").
+    ///
+    /// [`IdArray::new`]: crate::driver::IdArray::new
+    unsafe fn id_info_from_offset<Id>(id: *const Id, offset: isize) -> Option<&'static T::IdInfo> {
+        if offset == 0 {
+            return None;
+        }
+
+        // SAFETY: The offset comes from a previous call to `offset_from` in `IdArray::new`,
+        // which guarantees that the resulting pointer is within the table, as required by the
+        // safety requirements of this function.
+        let ptr = unsafe { (id as *const u8).offset(offset).cast::<Option<T::IdInfo>>() };
+
+        // SAFETY: The id table has a static lifetime, so `ptr` is guaranteed to be valid for read.
+        unsafe { (&*ptr).as_ref() }
+    }
+
+    /// Matches `client` against, in order, the OF, ACPI, and legacy i2c id tables this driver
+    /// was registered with.
+    ///
+    /// OF and ACPI are tried first, before the legacy i2c table, deliberately mirroring the
+    /// order the i2c core itself uses to match a newly probed client (`i2c_device_probe` checks
+    /// the firmware-described match before falling back to `i2c_match_id`), not the reverse.
     fn get_id_info(client: &Client) -> Option<&'static T::IdInfo> {
+        if let Some(table) = T::OF_DEVICE_ID_TABLE {
+            // SAFETY: `table.as_ref()` has static lifetime and `client.raw_device()` is valid
+            // for the duration of this call.
+            let id = unsafe { bindings::of_match_device(table.as_ref(), client.raw_device()) };
+            if !id.is_null() {
+                // SAFETY: `id` is a pointer within the static table, so it's always valid, and
+                // `data` holds the offset stashed by `to_rawid`.
+                let offset = unsafe { (*id).data } as isize;
+                // SAFETY: see `id_info_from_offset`.
+                return unsafe { Self::id_info_from_offset(id, offset) };
+            }
+        }
+
+        if let Some(table) = T::ACPI_DEVICE_ID_TABLE {
+            // SAFETY: `table.as_ref()` has static lifetime and `client.raw_device()` is valid
+            // for the duration of this call.
+            let id = unsafe { bindings::acpi_match_device(table.as_ref(), client.raw_device()) };
+            if !id.is_null() {
+                // SAFETY: `id` is a pointer within the static table, so it's always valid.
+                let offset = unsafe { (*id).driver_data } as isize;
+                // SAFETY: see `id_info_from_offset`.
+                return unsafe { Self::id_info_from_offset(id, offset) };
+            }
+        }
+
         let table = T::I2C_DEVICE_ID_TABLE?;
 
+        // SAFETY: `table.as_ref()` has static lifetime and `client.ptr` is valid by `Client`'s
+        // type invariants.
         let id = unsafe { bindings::i2c_match_id(table.as_ref(), client.ptr) };
         if id.is_null() {
             return None;
         }
 
         // SAFETY: `id` is a pointer within the static table, so it's always valid.
-        let offset = unsafe { (*id).driver_data };
-        if offset == 0 {
-            return None;
-        }
-
-        // SAFETY: The offset comes from a previous call to `offset_from` in `IdArray::new`, which
-        // guarantees that the resulting pointer is within the table.
-        let ptr = unsafe {
-            id.cast::<u8>()
-                .offset(offset as _)
-                .cast::<Option<T::IdInfo>>()
-        };
-
-        // SAFETY: The id table has a static lifetime, so `ptr` is guaranteed to be valid for read.
-        unsafe { (&*ptr).as_ref() }
+        let offset = unsafe { (*id).driver_data } as isize;
+        // SAFETY: see `id_info_from_offset`.
+        unsafe { Self::id_info_from_offset(id, offset) }
     }
 
     extern "C" fn probe_callback(i2c: *mut bindings::i2c_client) -> core::ffi::c_int {
         from_kernel_result! {
-            let mut client = unsafe { Client::from_ptr(i2c) };
+            let mut client = unsafe { Client::from_raw(i2c) };
             let info = Self::get_id_info(&client);
             let data = T::probe(&mut client, info)?;
 
@@ -172,6 +230,19 @@ pub trait Driver {
     /// The table of device ids supported by the driver.
     const I2C_DEVICE_ID_TABLE: Option<driver::IdTable<'static, DeviceId, Self::IdInfo>> = None;
 
+    /// The table of device tree `compatible` strings supported by the driver.
+    ///
+    /// The i2c core tries to match a newly probed client against this table before
+    /// [`Self::ACPI_DEVICE_ID_TABLE`] and [`Self::I2C_DEVICE_ID_TABLE`].
+    const OF_DEVICE_ID_TABLE: Option<driver::IdTable<'static, of::DeviceId, Self::IdInfo>> = None;
+
+    /// The table of ACPI device ids supported by the driver.
+    ///
+    /// The i2c core tries to match a newly probed client against this table before
+    /// [`Self::I2C_DEVICE_ID_TABLE`], but after [`Self::OF_DEVICE_ID_TABLE`].
+    const ACPI_DEVICE_ID_TABLE: Option<driver::IdTable<'static, acpi::DeviceId, Self::IdInfo>> =
+        None;
+
     /// I2C driver probe.
     ///
     /// Called when a new i2c client is added or discovered.
@@ -193,6 +264,16 @@ pub struct Client {
     ptr: *mut bindings::i2c_client,
 }
 
+// SAFETY: `Client` only holds a pointer to a C device, which is safe to be used from any
+// thread, and the `i2c_client` it points to is refcounted by the i2c core for as long as the
+// driver is bound, which outlives any handle the driver stashes away (e.g. as data for another
+// subsystem's callbacks).
+unsafe impl Send for Client {}
+
+// SAFETY: all `Client` methods take `&self`/`&mut self` and operate through C APIs that are
+// safe to call concurrently from multiple threads.
+unsafe impl Sync for Client {}
+
 impl Client {
     /// Creates a new client from the given pointer.
     ///
@@ -200,11 +281,30 @@ impl Client {
     ///
     /// `ptr` must be non-null and valid. It must remain valid for the lifetime of the returned
     /// instance.
-    unsafe fn from_ptr(ptr: *mut bindings::i2c_client) -> Self {
+    ///
+    /// `Client` is deliberately not `Copy`/`Clone`: every bus-transfer method except
+    /// [`Self::transfer_buffer_flags`] takes `&mut self`, so issuing a transfer through any of
+    /// them requires a `&mut Client`. Reconstructing a second handle from the same raw pointer
+    /// via this function defeats that exclusivity, so
+    /// callers that need to stash a client for another subsystem's callbacks (e.g.
+    /// [`crate::watchdog::WatchdogOps`]) should keep the raw pointer (see [`Self::as_raw`]) and
+    /// only call this function where they can guarantee no other `Client` handle for the same
+    /// device is live at the same time.
+    pub unsafe fn from_raw(ptr: *mut bindings::i2c_client) -> Self {
         // INVARIANT: The safety requirements of the function ensure the lifetime invariant.
         Self { ptr }
     }
 
+    /// Returns the raw `i2c_client` pointer backing this handle.
+    ///
+    /// This is the escape hatch for stashing a client as driver data for another subsystem
+    /// (e.g. a [`crate::watchdog::WatchdogOps`] implementation) without relying on `Client`
+    /// being `Copy`. Turn it back into a handle with the `unsafe` [`Self::from_raw`], at the
+    /// point where its safety requirements can actually be audited.
+    pub fn as_raw(&self) -> *mut bindings::i2c_client {
+        self.ptr
+    }
+
     /// Get Chip address.
     pub fn get_addr(&self) -> u16 {
         // SAFETY: By the type invariants, we know that `self.ptr` is non-null and valid.
@@ -253,6 +353,607 @@ impl Client {
     pub fn master_recv(&mut self, buf: &mut [u8]) -> Result<usize> {
         self.transfer_buffer_flags(buf, msg_flags::RD)
     }
+
+    /// Performs a combined transfer of several messages as a single, uninterrupted bus
+    /// transaction: the canonical idiom being a register-address write immediately followed by
+    /// a repeated-START read, e.g.
+    ///
+    /// ```ignore
+    /// let reg = [0x42];
+    /// let mut val = [0; 2];
+    /// client.transfer(&mut [Message::write(addr, &reg), Message::read(addr, &mut val)])?;
+    /// ```
+    ///
+    /// Every message keeps its buffer borrowed for as long as the `Message` lives, so mixing
+    /// read and write buffers in the same call can never alias: a [`Message::write`] only ever
+    /// holds a shared borrow of its buffer and a [`Message::read`] only ever holds a mutable
+    /// one, and both outlive this call.
+    pub fn transfer(&mut self, msgs: &mut [Message<'_>]) -> Result<usize> {
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid, so `adapter` is
+        // too. Each `Message` wraps a valid `i2c_msg` whose `buf` pointer is tied to the
+        // message's lifetime, which the caller must keep alive for the duration of this call;
+        // `Message` is `repr(transparent)` over `i2c_msg`, so the slice cast below is valid.
+        let ret = unsafe {
+            bindings::i2c_transfer(
+                (*self.ptr).adapter,
+                msgs.as_mut_ptr() as *mut bindings::i2c_msg,
+                msgs.len() as _,
+            )
+        };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Returns the bitmask of `I2C_FUNC_*` capabilities the underlying adapter supports, so
+    /// callers can check for `I2C_FUNC_SMBUS_*` support before calling the `smbus_*` methods
+    /// below, which already do so internally and return [`code::ENOTSUPP`] when unsupported.
+    pub fn functionality(&self) -> u32 {
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid, so `adapter` is
+        // too.
+        unsafe { bindings::i2c_get_functionality((*self.ptr).adapter) }
+    }
+
+    /// Returns [`code::ENOTSUPP`] unless the adapter advertises every bit set in `required`.
+    fn check_functionality(&self, required: u32) -> Result {
+        if self.functionality() & required == required {
+            Ok(())
+        } else {
+            Err(code::ENOTSUPP)
+        }
+    }
+
+    // Named `smbus_*` rather than the unprefixed `read_byte_data`/etc. so they read consistently
+    // alongside `master_send`/`master_recv` and the other `smbus_*` methods below, rather than
+    // leaving the SMBus and master-mode halves of this API looking unrelated.
+
+    /// Reads a byte from the given SMBus command (register).
+    pub fn smbus_read_byte_data(&mut self, command: u8) -> Result<u8> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_BYTE_DATA)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_read_byte_data(self.ptr, command) };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Writes a byte to the given SMBus command (register).
+    pub fn smbus_write_byte_data(&mut self, command: u8, value: u8) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_BYTE_DATA)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_write_byte_data(self.ptr, command, value) };
+        to_result(ret)?;
+        Ok(())
+    }
+
+    /// Reads a little-endian word from the given SMBus command (register).
+    pub fn smbus_read_word_data(&mut self, command: u8) -> Result<u16> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_WORD_DATA)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_read_word_data(self.ptr, command) };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Writes a little-endian word to the given SMBus command (register).
+    pub fn smbus_write_word_data(&mut self, command: u8, value: u16) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_WORD_DATA)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_write_word_data(self.ptr, command, value) };
+        to_result(ret)?;
+        Ok(())
+    }
+
+    /// Reads a single byte, without a preceding command (register) byte.
+    pub fn smbus_read_byte(&mut self) -> Result<u8> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_BYTE)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_read_byte(self.ptr) };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Writes a single byte, without a following command (register) byte.
+    pub fn smbus_write_byte(&mut self, value: u8) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_BYTE)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_write_byte(self.ptr, value) };
+        to_result(ret)?;
+        Ok(())
+    }
+
+    /// Writes `value` to the given SMBus command (register) and returns the word the device
+    /// sends back in the same transaction.
+    pub fn smbus_process_call(&mut self, command: u8, value: u16) -> Result<u16> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_PROC_CALL)?;
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let ret = unsafe { bindings::i2c_smbus_process_call(self.ptr, command, value) };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Reads a variable-length SMBus block from the given SMBus command (register).
+    ///
+    /// The device sends the block length as its first reply byte; `buf` must be large enough
+    /// for the protocol maximum of [`bindings::I2C_SMBUS_BLOCK_MAX`] bytes since the length
+    /// isn't known up front. Returns the number of bytes actually read.
+    pub fn smbus_read_block_data(
+        &mut self,
+        command: u8,
+        buf: &mut [u8; bindings::I2C_SMBUS_BLOCK_MAX as usize],
+    ) -> Result<usize> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_BLOCK_DATA)?;
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid. `buf` is valid for
+        // at least `I2C_SMBUS_BLOCK_MAX` writes.
+        let ret =
+            unsafe { bindings::i2c_smbus_read_block_data(self.ptr, command, buf.as_mut_ptr()) };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Writes a variable-length SMBus block to the given SMBus command (register).
+    ///
+    /// `buf.len()` is sent ahead of `buf` as the block's length byte, so it must not exceed
+    /// [`bindings::I2C_SMBUS_BLOCK_MAX`].
+    pub fn smbus_write_block_data(&mut self, command: u8, buf: &[u8]) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_BLOCK_DATA)?;
+        if buf.len() > bindings::I2C_SMBUS_BLOCK_MAX as usize {
+            return Err(code::EINVAL);
+        }
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid. `buf` is valid for
+        // reads for `buf.len()` bytes, which is what we pass as the block length.
+        let ret = unsafe {
+            bindings::i2c_smbus_write_block_data(self.ptr, command, buf.len() as _, buf.as_ptr())
+        };
+        to_result(ret)?;
+        Ok(())
+    }
+
+    /// Writes a fixed-length I2C block to the given SMBus command (register).
+    ///
+    /// Unlike [`Self::smbus_write_block_data`], no length byte is sent; `buf.len()` must still
+    /// not exceed [`bindings::I2C_SMBUS_BLOCK_MAX`].
+    pub fn smbus_write_i2c_block_data(&mut self, command: u8, buf: &[u8]) -> Result {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_WRITE_I2C_BLOCK)?;
+        if buf.len() > bindings::I2C_SMBUS_BLOCK_MAX as usize {
+            return Err(code::EINVAL);
+        }
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid. `buf` is valid for
+        // reads for `buf.len()` bytes, which is what we pass as the requested length.
+        let ret = unsafe {
+            bindings::i2c_smbus_write_i2c_block_data(
+                self.ptr,
+                command,
+                buf.len() as _,
+                buf.as_ptr(),
+            )
+        };
+        to_result(ret)?;
+        Ok(())
+    }
+
+    /// Reads a variable-length I2C block from the given SMBus command (register) into `buf`.
+    ///
+    /// Returns the number of bytes actually read, which is at most `buf.len()` and at most
+    /// [`bindings::I2C_SMBUS_BLOCK_MAX`].
+    pub fn smbus_read_i2c_block_data(&mut self, command: u8, buf: &mut [u8]) -> Result<usize> {
+        self.check_functionality(bindings::I2C_FUNC_SMBUS_READ_I2C_BLOCK)?;
+        if buf.len() > bindings::I2C_SMBUS_BLOCK_MAX as usize {
+            return Err(code::EINVAL);
+        }
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid. `buf` is valid for
+        // writes for `buf.len()` bytes, which is what we pass as the requested length.
+        let ret = unsafe {
+            bindings::i2c_smbus_read_i2c_block_data(
+                self.ptr,
+                command,
+                buf.len() as _,
+                buf.as_mut_ptr(),
+            )
+        };
+        to_result(ret)?;
+        Ok(ret as _)
+    }
+
+    /// Registers `backend` to answer slave-mode events addressed to this client.
+    ///
+    /// This lets a Rust driver act as an I2C slave device (e.g. an emulated EEPROM or other
+    /// responder), which is impossible with the master-only API above. `backend` is dropped by
+    /// [`Self::slave_unregister`].
+    ///
+    /// Slave registration and the `Driver::Data` set by `probe` share the single clientdata slot
+    /// the kernel gives each `i2c_client`, so this saves whatever is currently there and
+    /// restores it in [`Self::slave_unregister`]. Because of that, this **must not** be called
+    /// from [`Driver::probe`] itself: `probe_callback` only installs `Driver::Data` as
+    /// clientdata *after* `probe` returns, so a call made during `probe` would see no prior
+    /// clientdata to restore, and `probe_callback`'s unconditional `i2c_set_clientdata`
+    /// afterwards would then clobber (and leak) the backend just installed here. Call this only
+    /// once `probe` has returned successfully -- e.g. by stashing this client's raw pointer (see
+    /// [`Self::as_raw`]) in `Driver::Data` and reconstructing a `Client` from it later via
+    /// [`Self::from_raw`] -- and call [`Self::slave_unregister`] before `Driver::remove` returns,
+    /// so the clientdata is back to holding `Driver::Data` by the time the i2c core hands it to
+    /// `remove_callback`.
+    ///
+    /// Returns [`code::EINVAL`] if the clientdata slot is currently empty, which is the
+    /// observable symptom of calling this from `probe` as described above.
+    pub fn slave_register<T: SlaveBackend>(&mut self, backend: Box<T>) -> Result {
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let prev_clientdata = unsafe { bindings::i2c_get_clientdata(self.ptr) };
+        // A null clientdata means `Driver::Data` has not been installed yet, i.e. this is being
+        // called from `probe` itself, which would corrupt the clientdata slot once `probe`
+        // returns (see the doc above).
+        if prev_clientdata.is_null() {
+            return Err(code::EINVAL);
+        }
+        let ptr = Box::into_raw(Box::try_new(SlaveClientData {
+            backend,
+            prev_clientdata,
+        })?);
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid. `ptr` was just
+        // allocated by `Box::into_raw` above, and is read back as a `*const SlaveClientData<T>`
+        // only by `slave_callback`, installed right below.
+        unsafe { bindings::i2c_set_clientdata(self.ptr, ptr as _) };
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid, and
+        // `slave_callback::<T>` matches the `i2c_slave_cb_t` signature.
+        let ret =
+            unsafe { bindings::i2c_slave_register(self.ptr, Some(Self::slave_callback::<T>)) };
+        if let Err(e) = to_result(ret) {
+            // SAFETY: `ptr` was allocated just above and registration failed, so ownership of
+            // `SlaveClientData<T>` never passed to the core.
+            let inner = unsafe { Box::from_raw(ptr) };
+            // SAFETY: restores the clientdata this call found in place, now that registration
+            // has failed and nothing else has observed `ptr` as clientdata.
+            unsafe { bindings::i2c_set_clientdata(self.ptr, inner.prev_clientdata) };
+            drop(inner);
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Unregisters the backend previously installed by [`Self::slave_register`], dropping it and
+    /// restoring the clientdata `slave_register` found in place (typically the `Driver::Data`
+    /// set by `probe`).
+    ///
+    /// `T` must be the same type that was passed to [`Self::slave_register`].
+    pub fn slave_unregister<T: SlaveBackend>(&mut self) -> Result {
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        to_result(unsafe { bindings::i2c_slave_unregister(self.ptr) })?;
+
+        // SAFETY: `i2c_slave_unregister` having returned successfully, the core will not call
+        // `slave_callback` again, so the `SlaveClientData<T>` it read can be reclaimed.
+        let ptr = unsafe { bindings::i2c_get_clientdata(self.ptr) } as *mut SlaveClientData<T>;
+        // SAFETY: `ptr` was produced by `Box::into_raw` in `slave_register` and is only ever
+        // freed here.
+        let inner = unsafe { Box::from_raw(ptr) };
+
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid, and nothing has
+        // observed the clientdata slot since `i2c_slave_unregister` returned above.
+        unsafe { bindings::i2c_set_clientdata(self.ptr, inner.prev_clientdata) };
+        drop(inner);
+
+        Ok(())
+    }
+
+    /// Attaches `recovery` to this client's adapter, so the i2c core runs it to unwedge the bus
+    /// when a transfer times out with a slave holding SDA (or SCL) low.
+    ///
+    /// # Leak warning
+    ///
+    /// This permanently leaks `recovery` (both the `BusRecoveryInfo<T>` allocation and the
+    /// `T::Data` it was built from): `i2c_bus_recovery_info` has no unregister/detach API, so
+    /// there is no hook this binding can run to reclaim them, not even when the adapter itself
+    /// is torn down. Only call this for recovery info that legitimately needs to live for the
+    /// entire remaining lifetime of the kernel (e.g. a fixed GPIO-based recovery set up once at
+    /// `probe` time for a bus that outlives any conceivable unbind), and never from a path that
+    /// can run more than once for the same adapter.
+    pub fn set_bus_recovery<T: BusRecoveryOps>(&mut self, recovery: BusRecovery<T>) -> Result {
+        // SAFETY: By the type invariants, `self.ptr` is non-null and valid.
+        let adapter = unsafe { (*self.ptr).adapter };
+        if adapter.is_null() {
+            return Err(code::EINVAL);
+        }
+
+        let info = Box::into_raw(recovery.info);
+        // SAFETY: `adapter` is non-null, as checked above, and `info` was just leaked, so it
+        // outlives the adapter's use of it (the adapter is never unregistered before this
+        // client's own lifetime ends).
+        unsafe { (*adapter).bus_recovery_info = info as *mut bindings::i2c_bus_recovery_info };
+
+        Ok(())
+    }
+
+    unsafe extern "C" fn slave_callback<T: SlaveBackend>(
+        client: *mut bindings::i2c_client,
+        event: bindings::i2c_slave_event,
+        val: *mut u8,
+    ) -> core::ffi::c_int {
+        from_kernel_result! {
+            let event = SlaveEvent::from_raw(event).ok_or(code::EINVAL)?;
+            // SAFETY: `client`'s driver data was set to a `SlaveClientData<T>` pointer by
+            // `slave_register`, and is only ever freed by `slave_unregister`, which the i2c core
+            // guarantees is never called concurrently with (or before the last return of) this
+            // callback.
+            let data = unsafe { &*(bindings::i2c_get_clientdata(client) as *const SlaveClientData<T>) };
+            // SAFETY: The core guarantees `val` is valid for reads and writes for the duration
+            // of this call.
+            data.backend.slave_event(event, unsafe { &mut *val })?;
+            Ok(0)
+        }
+    }
+}
+
+/// Driver data installed on a client for as long as a [`SlaveBackend`] is registered on it via
+/// [`Client::slave_register`].
+///
+/// Holds the clientdata that was in place before registration (typically the `Driver::Data` set
+/// by `probe`), so [`Client::slave_unregister`] can put it back.
+struct SlaveClientData<T> {
+    backend: Box<T>,
+    prev_clientdata: *mut core::ffi::c_void,
+}
+
+/// I2C slave-mode bus events, as delivered by the kernel's `enum i2c_slave_event`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SlaveEvent {
+    /// The bus master is about to write; the backend may NAK by returning an error.
+    WriteRequested,
+    /// The bus master wrote the byte in `val`.
+    WriteReceived,
+    /// The bus master wants to read; the backend must place the byte to send in `val`.
+    ReadRequested,
+    /// The byte previously placed in `val` for [`Self::ReadRequested`] was sent; the backend
+    /// may place the next byte to send (for a multi-byte read) in `val`.
+    ReadProcessed,
+    /// The bus master issued a STOP condition, ending the transaction.
+    Stop,
+}
+
+impl SlaveEvent {
+    fn from_raw(event: bindings::i2c_slave_event) -> Option<Self> {
+        match event {
+            bindings::i2c_slave_event_I2C_SLAVE_WRITE_REQUESTED => Some(Self::WriteRequested),
+            bindings::i2c_slave_event_I2C_SLAVE_WRITE_RECEIVED => Some(Self::WriteReceived),
+            bindings::i2c_slave_event_I2C_SLAVE_READ_REQUESTED => Some(Self::ReadRequested),
+            bindings::i2c_slave_event_I2C_SLAVE_READ_PROCESSED => Some(Self::ReadProcessed),
+            bindings::i2c_slave_event_I2C_SLAVE_STOP => Some(Self::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// Implemented by types that respond to I2C slave-mode bus events, such as an emulated EEPROM
+/// or other responder device.
+///
+/// Install on a [`Client`] via [`Client::slave_register`].
+pub trait SlaveBackend: Sync {
+    /// Handles a single slave-mode event.
+    ///
+    /// For [`SlaveEvent::WriteReceived`], `val` carries the byte just written by the bus
+    /// master. For [`SlaveEvent::ReadRequested`] and [`SlaveEvent::ReadProcessed`],
+    /// implementations write the next byte to send back into `val`. `val` is unused for
+    /// [`SlaveEvent::WriteRequested`] and [`SlaveEvent::Stop`].
+    fn slave_event(&self, event: SlaveEvent, val: &mut u8) -> Result;
+}
+
+/// A single I2C message, as used by [`Client::transfer`].
+///
+/// This is the combined-transfer message type: the type itself and `Client::transfer` were
+/// already introduced for `Message::write`/`Message::read`, so `Message::dma_safe` and the
+/// existing `&mut self` `transfer` were extended in place rather than adding a second, near-
+/// identical `Msg` type and `transfer(&self, ...)` overload for the same C call.
+///
+/// # Invariants
+///
+/// `msg.buf` is valid for `msg.len` bytes, for writes (when `I2C_M_RD` is set in `msg.flags`) or
+/// reads (otherwise) as appropriate, for the lifetime `'a`.
+#[repr(transparent)]
+pub struct Message<'a> {
+    msg: bindings::i2c_msg,
+    _buf: marker::PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> Message<'a> {
+    /// Creates a message that writes `buf` to `addr`.
+    pub fn write(addr: u16, buf: &'a [u8]) -> Self {
+        // INVARIANT: `buf` is valid for `buf.len()` reads for at least `'a`, and `I2C_M_RD` is
+        // not set.
+        Self {
+            msg: bindings::i2c_msg {
+                addr,
+                flags: 0,
+                len: buf.len() as _,
+                buf: buf.as_ptr() as _,
+            },
+            _buf: marker::PhantomData,
+        }
+    }
+
+    /// Creates a message that reads into `buf` from `addr`.
+    pub fn read(addr: u16, buf: &'a mut [u8]) -> Self {
+        // INVARIANT: `buf` is valid for `buf.len()` writes for at least `'a`, and `I2C_M_RD` is
+        // set.
+        Self {
+            msg: bindings::i2c_msg {
+                addr,
+                flags: msg_flags::RD,
+                len: buf.len() as _,
+                buf: buf.as_mut_ptr(),
+            },
+            _buf: marker::PhantomData,
+        }
+    }
+
+    /// Marks this message's buffer as DMA-safe (i.e. not stack- or vmalloc-allocated), letting
+    /// the adapter DMA to/from it directly instead of bouncing through a bounce buffer.
+    pub fn dma_safe(mut self) -> Self {
+        self.msg.flags |= msg_flags::DMA_SAFE;
+        self
+    }
+}
+
+/// Implemented to recover a wedged I2C bus, where a slave is holding SDA (or SCL) low and needs
+/// to be clocked free.
+///
+/// Attach to an adapter via [`BusRecovery::new`] and [`Client::set_bus_recovery`]. The default
+/// [`Self::recover_bus`] runs the generic toggle-SCL algorithm (drive SCL high, pulse it low and
+/// high again up to [`Self::NUM_CLOCK_PULSES`] times while watching for the slave to release
+/// SDA, then issue a STOP condition), so most drivers only need to implement
+/// [`Self::get_scl`]/[`Self::set_scl`] (and, if available, [`Self::get_sda`]/[`Self::set_sda`])
+/// against their recovery GPIOs.
+#[vtable]
+pub trait BusRecoveryOps {
+    /// The type of the context data made available to every hook below, e.g. the recovery GPIO
+    /// descriptors.
+    type Data: PointerWrapper + Send + Sync = ();
+
+    /// Number of SCL clock pulses to issue while waiting for a wedged slave to release SDA.
+    const NUM_CLOCK_PULSES: u32 = 9;
+
+    /// Reads the current level of the SDA line.
+    fn get_sda(_data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result<bool> {
+        Err(code::EOPNOTSUPP)
+    }
+
+    /// Reads the current level of the SCL line.
+    fn get_scl(_data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result<bool> {
+        Err(code::EOPNOTSUPP)
+    }
+
+    /// Drives the SCL line to `high`.
+    fn set_scl(data: <Self::Data as PointerWrapper>::Borrowed<'_>, high: bool);
+
+    /// Drives the SDA line to `high`.
+    ///
+    /// Only needed for the default [`Self::recover_bus`] to be able to issue a STOP condition;
+    /// adapters that can only drive SCL may leave this unimplemented.
+    fn set_sda(_data: <Self::Data as PointerWrapper>::Borrowed<'_>, _high: bool) {}
+
+    /// Runs the whole recovery sequence.
+    ///
+    /// The default implements the kernel's generic SCL-toggling algorithm described on the
+    /// trait itself.
+    fn recover_bus(data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result {
+        Self::set_scl(data, true);
+        for _ in 0..Self::NUM_CLOCK_PULSES {
+            if matches!(Self::get_sda(data), Ok(true)) {
+                break;
+            }
+            Self::set_scl(data, false);
+            Self::set_scl(data, true);
+        }
+
+        // Issue a STOP condition: SDA transitions low-to-high while SCL is high.
+        Self::set_sda(data, false);
+        Self::set_scl(data, true);
+        Self::set_sda(data, true);
+
+        Ok(())
+    }
+}
+
+/// The data behind a registered [`BusRecoveryOps::Data`], plus the raw `i2c_bus_recovery_info`
+/// the kernel's `recover_bus`/`get_scl`/... callbacks are given a pointer to.
+///
+/// # Invariants
+///
+/// `raw` is the first field, so a `*mut bindings::i2c_bus_recovery_info` obtained from
+/// `&self.raw` may be cast back to `*mut BusRecoveryInfo<T>`.
+#[repr(C)]
+struct BusRecoveryInfo<T: BusRecoveryOps> {
+    raw: bindings::i2c_bus_recovery_info,
+    data: *mut core::ffi::c_void,
+    _p: marker::PhantomData<T>,
+}
+
+struct BusRecoveryVtable<T>(marker::PhantomData<T>);
+
+impl<T: BusRecoveryOps> BusRecoveryVtable<T> {
+    unsafe extern "C" fn get_scl_callback(adap: *mut bindings::i2c_adapter) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: the i2c core calls back with the `adap` this recovery info was attached
+            // to, whose `bus_recovery_info` was set to a `BusRecoveryInfo<T>` by
+            // `Client::set_bus_recovery`, and `data` was set from a `T::Data::into_pointer()` value
+            // by `BusRecovery::new`.
+            let info = unsafe { (*adap).bus_recovery_info as *mut BusRecoveryInfo<T> };
+            let data = unsafe { T::Data::borrow((*info).data) };
+            Ok(T::get_scl(data)? as i32)
+        }
+    }
+
+    unsafe extern "C" fn set_scl_callback(adap: *mut bindings::i2c_adapter, val: core::ffi::c_int) {
+        // SAFETY: see `get_scl_callback`.
+        let info = unsafe { (*adap).bus_recovery_info as *mut BusRecoveryInfo<T> };
+        // SAFETY: see `get_scl_callback`.
+        let data = unsafe { T::Data::borrow((*info).data) };
+        T::set_scl(data, val != 0);
+    }
+
+    unsafe extern "C" fn get_sda_callback(adap: *mut bindings::i2c_adapter) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: see `get_scl_callback`.
+            let info = unsafe { (*adap).bus_recovery_info as *mut BusRecoveryInfo<T> };
+            let data = unsafe { T::Data::borrow((*info).data) };
+            Ok(T::get_sda(data)? as i32)
+        }
+    }
+
+    unsafe extern "C" fn set_sda_callback(adap: *mut bindings::i2c_adapter, val: core::ffi::c_int) {
+        // SAFETY: see `get_scl_callback`.
+        let info = unsafe { (*adap).bus_recovery_info as *mut BusRecoveryInfo<T> };
+        // SAFETY: see `get_scl_callback`.
+        let data = unsafe { T::Data::borrow((*info).data) };
+        T::set_sda(data, val != 0);
+    }
+
+    unsafe extern "C" fn recover_bus_callback(
+        adap: *mut bindings::i2c_adapter,
+    ) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: see `get_scl_callback`.
+            let info = unsafe { (*adap).bus_recovery_info as *mut BusRecoveryInfo<T> };
+            let data = unsafe { T::Data::borrow((*info).data) };
+            T::recover_bus(data)?;
+            Ok(0)
+        }
+    }
+}
+
+/// A bus-recovery configuration, attached to a client's adapter via
+/// [`Client::set_bus_recovery`].
+///
+/// Wraps the kernel's `struct i2c_bus_recovery_info`.
+pub struct BusRecovery<T: BusRecoveryOps> {
+    info: Box<BusRecoveryInfo<T>>,
+}
+
+impl<T: BusRecoveryOps> BusRecovery<T> {
+    /// Builds a bus-recovery configuration backed by `data` (e.g. the recovery GPIO
+    /// descriptors).
+    pub fn new(data: T::Data) -> Result<Self> {
+        // SAFETY: all-zeroes is a valid value for `struct i2c_bus_recovery_info`, and a null
+        // `data` pointer is never dereferenced before it is overwritten below.
+        let mut info: Box<BusRecoveryInfo<T>> = unsafe { Box::try_new_zeroed()?.assume_init() };
+
+        info.data = data.into_pointer() as _;
+        if T::HAS_GET_SCL {
+            info.raw.get_scl = Some(BusRecoveryVtable::<T>::get_scl_callback);
+        }
+        info.raw.set_scl = Some(BusRecoveryVtable::<T>::set_scl_callback);
+        if T::HAS_GET_SDA {
+            info.raw.get_sda = Some(BusRecoveryVtable::<T>::get_sda_callback);
+        }
+        if T::HAS_SET_SDA {
+            info.raw.set_sda = Some(BusRecoveryVtable::<T>::set_sda_callback);
+        }
+        info.raw.recover_bus = Some(BusRecoveryVtable::<T>::recover_bus_callback);
+
+        Ok(Self { info })
+    }
 }
 
 // SAFETY: The device returned by `raw_device` is the raw i2c device.
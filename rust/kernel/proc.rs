@@ -5,16 +5,16 @@
 //! C header: [`include/linux/proc_fs.h`](../../../../include/linux/proc_fs.h)
 
 use alloc::boxed::Box;
-use core::{marker, mem, ptr};
+use core::{fmt, marker, mem, ptr};
 
 use crate::{
     bindings,
-    error::{code, from_kernel_result, Result},
+    error::{code, from_kernel_result, to_result, Result},
     file::{File, IoctlCommand, OpenAdapter, PollTable, SeekFrom},
     io_buffer::{IoBufferReader, IoBufferWriter},
     iov_iter::IovIter,
     mm,
-    str::CStr,
+    str::{CStr, CString},
     types::{Mode, PointerWrapper},
     user_ptr::UserSlicePtr,
 };
@@ -38,6 +38,13 @@ unsafe impl<T: Send> Send for ProcDirEntry<T> {}
 // references to which are safe to be used from any thread.
 unsafe impl<T: Sync> Sync for ProcDirEntry<T> {}
 
+/// Marker type for a [`ProcDirEntry`] created by [`ProcDirEntry::symlink`].
+///
+/// A symlink has no `proc_ops` of its own, so this type is uninhabited and deliberately does
+/// not implement [`Operations`]: it exists only to make `ProcDirEntry<Symlink>` reject the
+/// `Operations`-based API at compile time, rather than at runtime.
+pub enum Symlink {}
+
 impl ProcDirEntry {
     /// Get a pointer to the parent proc dir entry or null.
     fn parent_ptr(parent: Option<&ProcDirEntry>) -> *mut bindings::proc_dir_entry {
@@ -67,6 +74,57 @@ impl ProcDirEntry {
             marker: marker::PhantomData,
         })
     }
+
+    /// Creates a new seq_file-backed proc file entry, driven by a [`SeqOperations`] iterator
+    /// instead of a raw offset/buffer pair.
+    pub fn new_seq<T: SeqOperations>(
+        name: &CStr,
+        mode: Mode,
+        parent: Option<&ProcDirEntry>,
+        data: Box<T::OpenData>,
+    ) -> Result<Self> {
+        // SAFETY: the adapter is compatible with ProcDirEntry
+        let proc_ops = unsafe { SeqOperationsVtable::<T>::build() };
+
+        let parent_ptr = ProcDirEntry::parent_ptr(parent);
+
+        // SAFETY: name is valid an non-null
+        // SAFETY: parent_ptr is valid
+        // SAFETY: proc_ops is valid
+        let ptr = unsafe {
+            bindings::proc_create_data(
+                name.as_char_ptr(),
+                mode.as_int(),
+                parent_ptr,
+                proc_ops,
+                Box::into_raw(data) as _,
+            )
+        };
+
+        Ok(Self {
+            ptr: core::ptr::NonNull::new(ptr).ok_or(code::ENOMEM)?,
+            marker: marker::PhantomData,
+        })
+    }
+}
+
+impl ProcDirEntry<Symlink> {
+    /// Creates a symbolic link in procfs pointing at `dest`.
+    ///
+    /// Corresponds to `proc_symlink`.
+    pub fn symlink(name: &CStr, parent: Option<&ProcDirEntry>, dest: &CStr) -> Result<Self> {
+        let parent_ptr = ProcDirEntry::parent_ptr(parent);
+
+        // SAFETY: name and dest are valid and non-null.
+        // SAFETY: parent_ptr is valid.
+        let ptr =
+            unsafe { bindings::proc_symlink(name.as_char_ptr(), parent_ptr, dest.as_char_ptr()) };
+
+        Ok(Self {
+            ptr: core::ptr::NonNull::new(ptr).ok_or(code::ENOMEM)?,
+            marker: marker::PhantomData,
+        })
+    }
 }
 
 impl<T: Operations> ProcDirEntry<T> {
@@ -111,6 +169,24 @@ impl<T: Operations> OpenAdapter<T::OpenData> for ProcDirEntry<T> {
     }
 }
 
+impl<T> ProcDirEntry<T> {
+    /// Sets the `st_size` field reported by `stat(2)` on this entry.
+    ///
+    /// Corresponds to `proc_set_size`.
+    pub fn set_size(&self, size: bindings::loff_t) {
+        // SAFETY: `self.ptr` is valid by the type invariants.
+        unsafe { bindings::proc_set_size(self.ptr.as_ptr(), size) };
+    }
+
+    /// Sets the owning uid and gid reported by `stat(2)` on this entry.
+    ///
+    /// Corresponds to `proc_set_user`.
+    pub fn set_owner(&self, uid: Kuid, gid: Kgid) {
+        // SAFETY: `self.ptr` is valid by the type invariants.
+        unsafe { bindings::proc_set_user(self.ptr.as_ptr(), uid.0, gid.0) };
+    }
+}
+
 impl<T> Drop for ProcDirEntry<T> {
     fn drop(&mut self) {
         // SAFETY: `ptr` is valid by type invariants.
@@ -120,6 +196,30 @@ impl<T> Drop for ProcDirEntry<T> {
     }
 }
 
+/// A kernel user id, as seen from the initial user namespace.
+#[derive(Clone, Copy)]
+pub struct Kuid(bindings::kuid_t);
+
+impl Kuid {
+    /// Creates a `Kuid` from a raw uid in the initial user namespace.
+    pub fn from_uid(uid: u32) -> Self {
+        // SAFETY: `init_user_ns` has a static lifetime and is always valid.
+        Self(unsafe { bindings::make_kuid(&mut bindings::init_user_ns, uid) })
+    }
+}
+
+/// A kernel group id, as seen from the initial user namespace.
+#[derive(Clone, Copy)]
+pub struct Kgid(bindings::kgid_t);
+
+impl Kgid {
+    /// Creates a `Kgid` from a raw gid in the initial user namespace.
+    pub fn from_gid(gid: u32) -> Self {
+        // SAFETY: `init_user_ns` has a static lifetime and is always valid.
+        Self(unsafe { bindings::make_kgid(&mut bindings::init_user_ns, gid) })
+    }
+}
+
 pub(crate) struct OperationsVtable<A, T>(marker::PhantomData<A>, marker::PhantomData<T>);
 
 impl<A: OpenAdapter<T::OpenData>, T: Operations> OperationsVtable<A, T> {
@@ -512,3 +612,197 @@ pub trait Operations {
         Ok(bindings::POLLIN | bindings::POLLOUT | bindings::POLLRDNORM | bindings::POLLWRNORM)
     }
 }
+
+/// A safe wrapper around `struct seq_file`, passed to [`SeqOperations::show`].
+///
+/// # Invariants
+///
+/// The field `ptr` is valid for the duration of the call it was handed out in.
+pub struct SeqFile {
+    ptr: *mut bindings::seq_file,
+}
+
+impl SeqFile {
+    /// Creates a new wrapper from a valid `struct seq_file` pointer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null, valid, and must not be used after the call that handed it out
+    /// returns.
+    unsafe fn from_ptr(ptr: *mut bindings::seq_file) -> Self {
+        Self { ptr }
+    }
+
+    /// Appends raw bytes to the sequence output.
+    ///
+    /// Corresponds to `seq_write`. If the underlying buffer would overflow, the kernel discards
+    /// everything written for the current record and replays [`SeqOperations::show`] with a
+    /// larger buffer, so this can be called unconditionally without checking for space.
+    pub fn write_slice(&mut self, data: &[u8]) -> Result {
+        // SAFETY: `self.ptr` is valid by the type invariants. `data` is valid for `data.len()`
+        // bytes for the duration of this call.
+        unsafe { bindings::seq_write(self.ptr, data.as_ptr() as _, data.len() as _) };
+        Ok(())
+    }
+
+    /// Appends formatted text to the sequence output.
+    pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result {
+        let s = CString::try_from_fmt(args)?;
+        self.write_slice(s.as_bytes())
+    }
+}
+
+/// Corresponds to the kernel's `struct seq_operations`.
+///
+/// Implementing this trait and passing it to [`ProcDirEntry::new_seq`] gives a line-oriented
+/// proc file an iterator-shaped API instead of the raw offset/buffer pair that
+/// [`Operations::read`] exposes, removing the need for every proc file to hand-roll partial-read
+/// and offset bookkeeping.
+///
+/// # Invariants
+///
+/// If the `seq_file` buffer passed to [`Self::show`] overflows, the kernel discards the
+/// partially written record and calls [`Self::show`] again for the same `item` with a larger
+/// buffer. [`Self::show`] therefore only ever borrows `item`, so that this replay is always safe.
+pub trait SeqOperations {
+    /// The type of the context data passed in when the proc entry is created.
+    type OpenData: Sync = ();
+
+    /// The type of the values yielded while walking the sequence.
+    type Item;
+
+    /// Returns the first item at or after `pos`, or `None` once the sequence is exhausted.
+    fn start(data: &Self::OpenData, pos: bindings::loff_t) -> Option<Self::Item>;
+
+    /// Returns the item following `item`, or `None` once the sequence is exhausted.
+    fn next(
+        data: &Self::OpenData,
+        item: Self::Item,
+        pos: bindings::loff_t,
+    ) -> Option<Self::Item>;
+
+    /// Called once iteration over the sequence stops, handing back the last item (if any) that
+    /// was never passed to a following [`Self::next`] call.
+    fn stop(_data: &Self::OpenData, _item: Option<Self::Item>) {}
+
+    /// Formats `item` into `seq`.
+    ///
+    /// Must not mutate `item`; see the type's invariants.
+    fn show(item: &Self::Item, seq: &mut SeqFile) -> Result;
+}
+
+pub(crate) struct SeqOperationsVtable<T>(marker::PhantomData<T>);
+
+impl<T: SeqOperations> SeqOperationsVtable<T> {
+    /// # Safety
+    ///
+    /// `m` must be a valid, non-null pointer to a `seq_file` created by [`Self::open_callback`],
+    /// and `pos` must be valid for reads and writes.
+    unsafe extern "C" fn start_callback(
+        m: *mut bindings::seq_file,
+        pos: *mut bindings::loff_t,
+    ) -> *mut core::ffi::c_void {
+        // SAFETY: `m.private` was set to the `T::OpenData` pointer by `open_callback` and is
+        // never mutated afterwards.
+        let data = unsafe { &*((*m).private as *const T::OpenData) };
+        match T::start(data, unsafe { *pos }) {
+            Some(item) => Box::into_raw(Box::new(item)) as _,
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe extern "C" fn next_callback(
+        m: *mut bindings::seq_file,
+        v: *mut core::ffi::c_void,
+        pos: *mut bindings::loff_t,
+    ) -> *mut core::ffi::c_void {
+        // SAFETY: `v` was previously returned by `start_callback` or this function, which always
+        // box allocated a live `T::Item`.
+        let item = unsafe { *Box::from_raw(v as *mut T::Item) };
+        // SAFETY: see `start_callback`.
+        let data = unsafe { &*((*m).private as *const T::OpenData) };
+        unsafe { *pos += 1 };
+        match T::next(data, item, unsafe { *pos }) {
+            Some(item) => Box::into_raw(Box::new(item)) as _,
+            None => ptr::null_mut(),
+        }
+    }
+
+    unsafe extern "C" fn stop_callback(m: *mut bindings::seq_file, v: *mut core::ffi::c_void) {
+        // SAFETY: see `start_callback`.
+        let data = unsafe { &*((*m).private as *const T::OpenData) };
+        let item = if v.is_null() {
+            None
+        } else {
+            // SAFETY: see `next_callback`.
+            Some(unsafe { *Box::from_raw(v as *mut T::Item) })
+        };
+        T::stop(data, item);
+    }
+
+    unsafe extern "C" fn show_callback(
+        m: *mut bindings::seq_file,
+        v: *mut core::ffi::c_void,
+    ) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: `v` was previously returned by `start_callback`/`next_callback` and is
+            // owned by the core until `stop_callback` is called, so it is safe to borrow here.
+            let item = unsafe { &*(v as *const T::Item) };
+            // SAFETY: `m` is valid for the duration of this call.
+            let mut seq = unsafe { SeqFile::from_ptr(m) };
+            T::show(item, &mut seq)?;
+            Ok(0)
+        }
+    }
+
+    const SEQ_OPS: bindings::seq_operations = bindings::seq_operations {
+        start: Some(Self::start_callback),
+        next: Some(Self::next_callback),
+        stop: Some(Self::stop_callback),
+        show: Some(Self::show_callback),
+    };
+
+    unsafe extern "C" fn open_callback(
+        inode: *mut bindings::inode,
+        file: *mut bindings::file,
+    ) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: `inode` is valid for the duration of this call and was created with the
+            // `T::OpenData` pointer passed to `proc_create_data` by `ProcDirEntry::new_seq`.
+            let data = unsafe { bindings::pde_data(inode) };
+            // SAFETY: `file` is valid and `&Self::SEQ_OPS` has static lifetime.
+            to_result(unsafe { bindings::seq_open(file, &Self::SEQ_OPS) })?;
+            // SAFETY: `seq_open` succeeded, so `file.private_data` now points at a valid
+            // `struct seq_file`.
+            let m = unsafe { (*file).private_data } as *mut bindings::seq_file;
+            // SAFETY: `m` is valid and exclusively owned at this point.
+            unsafe { (*m).private = data };
+            Ok(0)
+        }
+    }
+
+    const VTABLE: bindings::proc_ops = bindings::proc_ops {
+        proc_flags: 0, // FIXME: real value
+        proc_open: Some(Self::open_callback),
+        proc_read: Some(bindings::seq_read),
+        proc_read_iter: None,
+        proc_write: None,
+        proc_lseek: Some(bindings::seq_lseek),
+        proc_release: Some(bindings::seq_release),
+        proc_poll: None,
+        proc_ioctl: None,
+        #[cfg(CONFIG_COMPAT)]
+        proc_compat_ioctl: None,
+        proc_mmap: None,
+        proc_get_unmapped_area: None,
+    };
+
+    /// Builds an instance of [`struct proc_ops`] for a seq_file-backed proc entry.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that `T` is compatible with the way the device is registered.
+    pub(crate) const unsafe fn build() -> &'static bindings::proc_ops {
+        &Self::VTABLE
+    }
+}
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: GPL-2.0
+
+//! Watchdog devices and drivers.
+//!
+//! C header: [`include/linux/watchdog.h`](../../../../include/linux/watchdog.h)
+
+use alloc::boxed::Box;
+use core::marker;
+
+use macros::vtable;
+
+use crate::{
+    bindings,
+    device::RawDevice,
+    error::{code, from_kernel_result, to_result, Result},
+    str::CStr,
+    types::PointerWrapper,
+    ThisModule,
+};
+
+/// Corresponds to the kernel's `struct watchdog_ops`.
+///
+/// Implement this trait and pass it to [`WatchdogDevice::register`] to back a `/dev/watchdogN`
+/// device with the standard ping-on-write semantics and `WDIOC_*` ioctls handled by the
+/// watchdog core.
+#[vtable]
+pub trait WatchdogOps {
+    /// The type of the context data made available to every callback below.
+    ///
+    /// Set up before [`WatchdogDevice::register`] and released once the device is dropped.
+    type Data: PointerWrapper + Send + Sync = ();
+
+    /// Starts the watchdog timer.
+    fn start(data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result;
+
+    /// Stops the watchdog timer.
+    fn stop(data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result;
+
+    /// Pings (kicks) the watchdog, restarting its countdown.
+    fn ping(_data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Result {
+        Err(code::EOPNOTSUPP)
+    }
+
+    /// Changes the timeout, in seconds, after which the watchdog fires.
+    fn set_timeout(
+        _data: <Self::Data as PointerWrapper>::Borrowed<'_>,
+        _timeout: u32,
+    ) -> Result {
+        Err(code::EOPNOTSUPP)
+    }
+
+    /// Returns the number of seconds left before the watchdog fires, if known.
+    fn get_timeleft(_data: <Self::Data as PointerWrapper>::Borrowed<'_>) -> Option<u32> {
+        None
+    }
+}
+
+struct WatchdogOpsVtable<T>(marker::PhantomData<T>);
+
+impl<T: WatchdogOps> WatchdogOpsVtable<T> {
+    unsafe extern "C" fn start_callback(wdd: *mut bindings::watchdog_device) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: `wdd`'s driver data was set to a `T::Data::into_pointer()` value by
+            // `WatchdogDevice::register` and hasn't been freed, since that only happens once
+            // the device is unregistered.
+            let data = unsafe { T::Data::borrow(bindings::watchdog_get_drvdata(wdd) as _) };
+            T::start(data)?;
+            Ok(0)
+        }
+    }
+
+    unsafe extern "C" fn stop_callback(wdd: *mut bindings::watchdog_device) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: see `start_callback`.
+            let data = unsafe { T::Data::borrow(bindings::watchdog_get_drvdata(wdd) as _) };
+            T::stop(data)?;
+            Ok(0)
+        }
+    }
+
+    unsafe extern "C" fn ping_callback(wdd: *mut bindings::watchdog_device) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: see `start_callback`.
+            let data = unsafe { T::Data::borrow(bindings::watchdog_get_drvdata(wdd) as _) };
+            T::ping(data)?;
+            Ok(0)
+        }
+    }
+
+    unsafe extern "C" fn set_timeout_callback(
+        wdd: *mut bindings::watchdog_device,
+        timeout: core::ffi::c_uint,
+    ) -> core::ffi::c_int {
+        from_kernel_result! {
+            // SAFETY: see `start_callback`.
+            let data = unsafe { T::Data::borrow(bindings::watchdog_get_drvdata(wdd) as _) };
+            T::set_timeout(data, timeout as _)?;
+            Ok(0)
+        }
+    }
+
+    unsafe extern "C" fn get_timeleft_callback(
+        wdd: *mut bindings::watchdog_device,
+    ) -> core::ffi::c_uint {
+        // SAFETY: see `start_callback`.
+        let data = unsafe { T::Data::borrow(bindings::watchdog_get_drvdata(wdd) as _) };
+        T::get_timeleft(data).unwrap_or(0) as _
+    }
+
+    /// Builds an instance of `struct watchdog_ops`, with `owner` set to `module` so the watchdog
+    /// core can pin this module for as long as `/dev/watchdogN` is open.
+    ///
+    /// Unlike the function-pointer fields (which only depend on `T`), `owner` depends on which
+    /// driver module is registering the device, so this returns an owned value instead of a
+    /// `&'static` shared table.
+    fn build(module: &'static ThisModule) -> bindings::watchdog_ops {
+        bindings::watchdog_ops {
+            owner: module.0,
+            start: Some(Self::start_callback),
+            stop: Some(Self::stop_callback),
+            ping: if T::HAS_PING {
+                Some(Self::ping_callback)
+            } else {
+                None
+            },
+            status: None,
+            set_timeout: if T::HAS_SET_TIMEOUT {
+                Some(Self::set_timeout_callback)
+            } else {
+                None
+            },
+            set_pretimeout: None,
+            get_timeleft: if T::HAS_GET_TIMELEFT {
+                Some(Self::get_timeleft_callback)
+            } else {
+                None
+            },
+            restart: None,
+            ioctl: None,
+        }
+    }
+}
+
+/// A registered watchdog device, exposing `/dev/watchdogN` to userspace.
+///
+/// # Invariants
+///
+/// `wdd` is valid and was successfully passed to `watchdog_register_device` for the lifetime of
+/// the object.
+pub struct WatchdogDevice<T: WatchdogOps> {
+    wdd: Box<bindings::watchdog_device>,
+    _info: Box<bindings::watchdog_info>,
+    _ops: Box<bindings::watchdog_ops>,
+    _p: marker::PhantomData<T>,
+}
+
+// SAFETY: `WatchdogDevice` only holds pointers to C structures and a `T::Data`, both of which
+// are safe to be used from any thread.
+unsafe impl<T: WatchdogOps> Send for WatchdogDevice<T> {}
+
+// SAFETY: the watchdog core serialises access to the registered device, so shared references
+// are safe to hand to other threads.
+unsafe impl<T: WatchdogOps> Sync for WatchdogDevice<T> {}
+
+impl<T: WatchdogOps> WatchdogDevice<T> {
+    /// Creates and registers a new watchdog device.
+    ///
+    /// `identity` is truncated to fit the kernel's `WATCHDOG_NAME_LEN`-sized `identity` field.
+    /// `module` is stored as `watchdog_ops::owner`, so the watchdog core can keep the module
+    /// pinned for as long as `/dev/watchdogN` stays open.
+    pub fn register(
+        parent: &dyn RawDevice,
+        identity: &CStr,
+        min_timeout: u32,
+        max_timeout: u32,
+        module: &'static ThisModule,
+        data: T::Data,
+    ) -> Result<Self> {
+        // SAFETY: all-zeroes is a valid value for `struct watchdog_info`.
+        let mut info: Box<bindings::watchdog_info> =
+            unsafe { Box::try_new_zeroed()?.assume_init() };
+        let name = identity.as_bytes();
+        let mut i = 0;
+        while i < name.len() && i < info.identity.len() - 1 {
+            info.identity[i] = name[i] as _;
+            i += 1;
+        }
+
+        let ops = Box::try_new(WatchdogOpsVtable::<T>::build(module))?;
+
+        // SAFETY: all-zeroes is a valid value for `struct watchdog_device`.
+        let mut wdd: Box<bindings::watchdog_device> =
+            unsafe { Box::try_new_zeroed()?.assume_init() };
+        wdd.parent = parent.raw_device();
+        wdd.info = &*info;
+        wdd.ops = &*ops;
+        wdd.min_timeout = min_timeout;
+        wdd.max_timeout = max_timeout;
+
+        // SAFETY: `wdd` is not yet registered, so no callback can race with this write.
+        unsafe { bindings::watchdog_set_drvdata(&mut *wdd, data.into_pointer() as _) };
+
+        // SAFETY: `wdd` is valid and exclusively owned up to this call; the watchdog core
+        // keeps using it for as long as the device stays registered, which is upheld by
+        // `Drop` calling `watchdog_unregister_device` before `wdd` is freed.
+        to_result(unsafe { bindings::watchdog_register_device(&mut *wdd) })?;
+
+        // INVARIANT: `wdd` was just registered successfully.
+        Ok(Self {
+            wdd,
+            _info: info,
+            _ops: ops,
+            _p: marker::PhantomData,
+        })
+    }
+}
+
+impl<T: WatchdogOps> Drop for WatchdogDevice<T> {
+    fn drop(&mut self) {
+        // SAFETY: `self.wdd` is valid and registered by the type invariants.
+        unsafe { bindings::watchdog_unregister_device(&mut *self.wdd) };
+
+        // SAFETY: the watchdog core no longer calls back into this device after
+        // `watchdog_unregister_device` returns, so the driver data can be reclaimed. It was
+        // set from a `T::Data::into_pointer()` value in `register` and never freed since.
+        let ptr = unsafe { bindings::watchdog_get_drvdata(&mut *self.wdd) };
+        // SAFETY: see above.
+        drop(unsafe { T::Data::from_pointer(ptr as _) });
+    }
+}